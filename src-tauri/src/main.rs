@@ -1,17 +1,17 @@
 // Prevents additional console window on Windows in release, DO NOT REMOVE!!
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::process::{Child, Command, Stdio};
 use std::sync::{Arc, Mutex, OnceLock};
 use std::thread;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use std::path::{Path, PathBuf};
 use std::env;
 use std::fs::{File, OpenOptions};
-use std::io::{Read, Write};
+use std::io::{BufRead, BufReader, Read, Write};
 
-use tauri::{Manager, State, Window};
+use tauri::{GlobalShortcutManager, Manager, State, Window};
 use serde::{Deserialize, Serialize};
 use tokio::time::sleep;
 
@@ -20,6 +20,12 @@ mod vault_rotation;
 // Single-instance lock management
 static INSTANCE_LOCK: OnceLock<Option<File>> = OnceLock::new();
 
+// Fallback used wherever `HEARTHLINK_VAULT_KEY` isn't set: both the child's
+// own env (`start_service`) and the `hearthlink://` proxy's outbound auth
+// header (`proxy::handle`) need to agree on the same value, or the proxy
+// would send a key the child was never started with.
+const DEFAULT_VAULT_KEY: &str = "yFLl9T3j6l_rsrgSIHMDqr5O_vt62MdpkJuhIEuilAM=";
+
 // Port profiles for different environments
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum PortProfile {
@@ -36,7 +42,21 @@ impl PortProfile {
             PortProfile::Dev => (8020, 8021, 8022, 8908),
         }
     }
-    
+
+    /// Resolve a registry entry's `port_key` to an actual port: the four
+    /// built-in keys come from this profile, anything else is taken as a
+    /// literal port number so a registered service can pin its own.
+    fn resolve_port(&self, port_key: &str) -> Option<u16> {
+        let (core_port, vault_port, synapse_port, alden_port) = self.get_ports();
+        match port_key {
+            "core" => Some(core_port),
+            "vault" => Some(vault_port),
+            "synapse" => Some(synapse_port),
+            "alden" => Some(alden_port),
+            other => other.parse().ok(),
+        }
+    }
+
     fn from_env() -> Self {
         match env::var("HEARTHLINK_PORT_PROFILE").unwrap_or_default().as_str() {
             "qa" => PortProfile::Qa,
@@ -129,6 +149,12 @@ pub struct ServiceStatus {
     pub restart_count: u32,
     pub last_restart: Option<u64>,
     pub restart_backoff: u64, // seconds to wait before next restart
+    pub consecutive_failures: u32,
+    // Updated whenever something expresses interest in the service
+    // (a Tauri command, or a `hearthlink://` proxied request). Drives the
+    // on-demand idle reaper: a running on-demand service whose
+    // `last_active` falls too far behind gets stopped until next needed.
+    pub last_active: Option<u64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -138,13 +164,781 @@ pub struct SystemHealth {
     pub startup_time: u64,
 }
 
+/// Lifecycle state broadcast via the `service-state-changed` event whenever
+/// the health monitor or backoff restarter moves a service between states.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum ServiceLifecycleState {
+    Up,
+    Unhealthy,
+    Restarting,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ServiceStateChangedEvent {
+    name: String,
+    state: ServiceLifecycleState,
+}
+
+/// One entry in a service's crash history: when it happened, the process
+/// exit code if one was available, the restart attempt it triggered, and
+/// the health-check error (if any) that led to the restart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrashRecord {
+    pub timestamp: u64,
+    pub exit_status: Option<i32>,
+    pub restart_count: u32,
+    pub last_health_error: Option<String>,
+}
+
+// Most crash records kept per service; old entries roll off like the log ring.
+const CRASH_HISTORY_CAPACITY: usize = 50;
+
+// Number of most-recent log lines kept in memory per service.
+const SERVICE_LOG_RING_CAPACITY: usize = 500;
+
 // Service process management
 pub struct ServiceManager {
     processes: Arc<Mutex<HashMap<String, Child>>>,
     services: Arc<Mutex<HashMap<String, ServiceStatus>>>,
+    // Rolling in-memory tail of each service's tagged stdout/stderr lines,
+    // exposed via `get_service_logs`. Mirrored to a per-service log file.
+    logs: Arc<Mutex<HashMap<String, VecDeque<String>>>>,
+    // Unix only: sockets the Rust side pre-binds and hands to each child
+    // via LISTEN_FDS, kept open across restarts so the kernel accept
+    // queue never closes (see `ensure_listener`/`socket_activation`).
+    listeners: Arc<Mutex<HashMap<String, std::net::TcpListener>>>,
+    // Services left un-spawned until first use; see `ensure_service_running`.
+    on_demand_services: Arc<Mutex<std::collections::HashSet<String>>>,
+    // Services currently subscribed via `start_service_logs`; gates whether
+    // the log drainer emits `service-log://{name}` events for live tailing.
+    log_subscribers: Arc<Mutex<std::collections::HashSet<String>>>,
+    // Crash/restart history per service, exposed via `get_service_crash_history`.
+    crash_records: Arc<Mutex<HashMap<String, Vec<CrashRecord>>>>,
+    // Set once in `main`'s `.setup()`; used to emit log/state-change events.
+    app_handle: Arc<Mutex<Option<tauri::AppHandle>>>,
     startup_time: u64,
-    port_profile: PortProfile,
+    // Mutex so SIGHUP can swap in a freshly re-read profile for a rolling
+    // restart without needing a whole new ServiceManager.
+    port_profile: Arc<Mutex<PortProfile>>,
     shutdown_in_progress: Arc<Mutex<bool>>,
+    // Data-driven service list (name, script, port key, readiness/liveness
+    // probes, env, args), seeded from the built-ins and extended by
+    // `services.toml` or `register_service`. `service_descriptor` resolves an
+    // entry to its script, port and probes under the current port profile.
+    service_registry: Arc<Mutex<Vec<ServiceRegistryEntry>>>,
+    // Crash-loop cap for `restart_service_with_backoff`; see `max_restart_attempts_from_env`.
+    max_restart_attempts: u32,
+}
+
+// systemd-style socket activation: the Rust process pre-binds each
+// service's TcpListener and hands the fd to the Python child via the
+// LISTEN_FDS/LISTEN_PID environment convention, instead of the child
+// binding its own port. This keeps the listening socket (and its accept
+// queue) alive across `restart_service_with_backoff`, closing the window
+// where in-flight connections would otherwise be dropped. Windows has no
+// equivalent fd-inheritance convention wired up here, so it keeps
+// binding in the child as before.
+#[cfg(unix)]
+mod socket_activation {
+    use std::os::unix::io::RawFd;
+
+    // sd_listen_fds() scans for inherited sockets starting at this fd.
+    pub const LISTEN_FDS_START: RawFd = 3;
+
+    pub fn clear_cloexec(fd: RawFd) -> std::io::Result<()> {
+        let flags = unsafe { libc::fcntl(fd, libc::F_GETFD) };
+        if flags < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        let result = unsafe { libc::fcntl(fd, libc::F_SETFD, flags & !libc::FD_CLOEXEC) };
+        if result < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+// The graceful shutdown path only ran when Tauri tore down the window; a
+// direct SIGTERM/SIGINT (systemctl stop, Ctrl-C, container stop) would skip
+// it entirely and orphan the Python children. `signals::install` wires the
+// same `stop_all_services` path into OS signals, and SIGHUP triggers a
+// rolling restart instead of a full teardown. Windows has no POSIX signal
+// story here, so it keeps relying on the window-close handler alone.
+#[cfg(unix)]
+mod signals {
+    use super::ServiceManager;
+    use std::path::PathBuf;
+    use tokio::signal::unix::{signal, SignalKind};
+
+    /// Install SIGTERM/SIGINT/SIGHUP handlers on a background Tokio task.
+    /// SIGTERM/SIGINT call `stop_all_services` exactly once and exit;
+    /// SIGHUP re-reads config and rolling-restarts instead.
+    pub fn install(manager: ServiceManager, resource_dir: PathBuf) {
+        tokio::spawn(async move {
+            let mut sigterm = signal(SignalKind::terminate()).expect("failed to install SIGTERM handler");
+            let mut sigint = signal(SignalKind::interrupt()).expect("failed to install SIGINT handler");
+            let mut sighup = signal(SignalKind::hangup()).expect("failed to install SIGHUP handler");
+
+            loop {
+                tokio::select! {
+                    _ = sigterm.recv() => {
+                        println!("🛑 SIGTERM received, shutting down");
+                        manager.stop_all_services();
+                        std::process::exit(0);
+                    }
+                    _ = sigint.recv() => {
+                        println!("🛑 SIGINT received, shutting down");
+                        manager.stop_all_services();
+                        std::process::exit(0);
+                    }
+                    _ = sighup.recv() => {
+                        manager.rolling_restart(resource_dir.clone());
+                    }
+                }
+            }
+        });
+    }
+}
+
+// System tray showing live per-service status, with per-service
+// Restart/View Logs menu items and a Restart All entry, driven by
+// `ServiceManager` the same way the rest of the app is.
+mod tray {
+    use super::ServiceManager;
+    use tauri::{AppHandle, CustomMenuItem, Manager, SystemTray, SystemTrayEvent, SystemTrayMenu, SystemTrayMenuItem};
+
+    const SHOW_ID: &str = "show";
+    const RESTART_ALL_ID: &str = "restart_all";
+    const RESTART_PREFIX: &str = "restart:";
+    const LOGS_PREFIX: &str = "logs:";
+
+    fn status_glyph(status: &str) -> &'static str {
+        match status {
+            "running" => "🟢",
+            "starting" => "🟡",
+            "error" => "🔴",
+            _ => "⚪",
+        }
+    }
+
+    /// Rebuild the menu from the current registry + status snapshot. Called
+    /// once at startup and again on every health-monitor tick so the
+    /// glyphs don't go stale.
+    pub fn build_menu(service_manager: &ServiceManager) -> SystemTrayMenu {
+        let mut menu = SystemTrayMenu::new()
+            .add_item(CustomMenuItem::new(SHOW_ID, "Show Hearthlink"))
+            .add_item(CustomMenuItem::new(RESTART_ALL_ID, "Restart All Services"))
+            .add_native_item(SystemTrayMenuItem::Separator);
+
+        let statuses = service_manager.services.lock().unwrap();
+        let mut names: Vec<String> = service_manager
+            .list_services()
+            .into_iter()
+            .map(|entry| entry.name)
+            .collect();
+        names.sort();
+
+        for name in names {
+            let status = statuses.get(&name).map(|s| s.status.as_str()).unwrap_or("stopped");
+            menu = menu
+                .add_item(
+                    CustomMenuItem::new(format!("status:{}", name), format!("{} {} — {}", status_glyph(status), name, status))
+                        .disabled(),
+                )
+                .add_item(CustomMenuItem::new(format!("{}{}", RESTART_PREFIX, name), "    Restart"))
+                .add_item(CustomMenuItem::new(format!("{}{}", LOGS_PREFIX, name), "    View Logs"));
+        }
+
+        menu
+    }
+
+    pub fn build() -> SystemTray {
+        SystemTray::new()
+    }
+
+    /// Refresh the tray menu in place; cheap enough to call from the
+    /// health-monitor loop every tick.
+    pub fn refresh(app_handle: &AppHandle, service_manager: &ServiceManager) {
+        let _ = app_handle.tray_handle().set_menu(build_menu(service_manager));
+    }
+
+    pub fn handle_event(app_handle: &AppHandle, event: SystemTrayEvent, resource_dir: &std::path::Path) {
+        let SystemTrayEvent::MenuItemClick { id, .. } = event else {
+            return;
+        };
+        let service_manager = app_handle.state::<ServiceManager>();
+
+        if id == SHOW_ID {
+            if let Some(window) = app_handle.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        } else if id == RESTART_ALL_ID {
+            service_manager.rolling_restart(resource_dir.to_path_buf());
+        } else if let Some(name) = id.strip_prefix(RESTART_PREFIX) {
+            service_manager.graceful_stop_service_enhanced(name);
+            if let Some(descriptor) = service_manager.service_descriptor(name) {
+                let _ = service_manager.start_service(name, &descriptor.script_path, descriptor.port, &descriptor.readiness.describe(), resource_dir);
+            }
+        } else if let Some(name) = id.strip_prefix(LOGS_PREFIX) {
+            let _ = app_handle.emit_all("open-service-logs", name);
+            if let Some(window) = app_handle.get_webview_window("main") {
+                let _ = window.show();
+                let _ = window.set_focus();
+            }
+        }
+
+        refresh(app_handle, &service_manager);
+    }
+}
+
+// In-process reverse proxy registered as the `hearthlink://` custom URI
+// scheme, so the webview can request `hearthlink://core/api/...` instead of
+// hard-coding `http://127.0.0.1:{port}`. Centralizes auth header injection
+// and turns a starting/unhealthy backend into a clean 503 instead of the
+// webview hitting a raw connection refused.
+mod proxy {
+    use super::{ServiceManager, DEFAULT_VAULT_KEY};
+    use tauri::http::{Request, Response, ResponseBuilder};
+    use tauri::{AppHandle, Manager};
+
+    pub fn handle(app: &AppHandle, request: &Request) -> Result<Response, Box<dyn std::error::Error>> {
+        let uri = request.uri();
+        let service_name = uri.host().unwrap_or_default().to_string();
+        let path_and_query = uri.path_and_query().map(|p| p.as_str()).unwrap_or("/");
+
+        let service_manager = app.state::<ServiceManager>();
+        // A proxied request to a running service counts as activity for
+        // the on-demand idle reaper.
+        let is_running = match service_manager.services.lock().unwrap().get_mut(&service_name) {
+            Some(s) if s.status == "running" => {
+                s.last_active = Some(super::now_secs());
+                true
+            }
+            _ => false,
+        };
+
+        let descriptor = is_running
+            .then(|| service_manager.service_descriptor(&service_name))
+            .flatten();
+
+        let Some(descriptor) = descriptor else {
+            // A stopped/idle-reaped on-demand service would otherwise 503
+            // forever, since nothing else was asking it to wake up — kick
+            // off a start here so the *next* request through this same
+            // `hearthlink://` path has a chance of landing on a running
+            // backend instead of needing a separate `ensure_service_running`
+            // call from the frontend.
+            if let Some(resource_dir) = app.path_resolver().resource_dir() {
+                if let Err(e) = service_manager.ensure_service_running(&service_name, &resource_dir) {
+                    eprintln!("proxy: failed to wake {} service: {}", service_name, e);
+                }
+            }
+
+            let body = format!(
+                "{{\"error\":\"{} is starting or unhealthy\",\"retry_after_ms\":500}}",
+                service_name
+            );
+            return ResponseBuilder::new()
+                .status(503)
+                .header("Retry-After", "1")
+                .header("Content-Type", "application/json")
+                .body(body.into_bytes());
+        };
+
+        let target = format!("http://127.0.0.1:{}{}", descriptor.port, path_and_query);
+
+        let vault_key = std::env::var("HEARTHLINK_VAULT_KEY")
+            .unwrap_or_else(|_| DEFAULT_VAULT_KEY.to_string());
+
+        let client = reqwest::blocking::Client::new();
+        let mut builder = client
+            .request(request.method().clone(), &target)
+            .header("X-Hearthlink-Vault-Key", vault_key);
+        for (name, value) in request.headers() {
+            if name == http::header::HOST {
+                continue;
+            }
+            builder = builder.header(name.clone(), value.clone());
+        }
+        if !request.body().is_empty() {
+            builder = builder.body(request.body().clone());
+        }
+
+        let upstream = builder.send()?;
+        let mut response = ResponseBuilder::new().status(upstream.status().as_u16());
+        for (name, value) in upstream.headers() {
+            response = response.header(name.clone(), value.clone());
+        }
+        response.body(upstream.bytes()?.to_vec())
+    }
+}
+
+// `graceful_stop_service_enhanced`/`stop_all_services` only reap the
+// children they explicitly `wait()` on; anything that exits outside that
+// path (e.g. a wait-handle thread that panicked before calling `wait()`)
+// would otherwise sit as a zombie forever. This background thread collects
+// any such exited child process-wide — but a blanket `waitpid(-1, ...)`
+// would just as happily steal the exit status of a still-tracked service
+// out from under `graceful_stop_service_enhanced`/`restart_service_with_backoff`,
+// which would then see `ECHILD` and log a bogus force-kill. So each tick
+// first drains every tracked `Child` through the normal std API (which
+// reaps it the "managed" way, leaving a cached status those call sites can
+// still read) while holding the same `processes` lock they use, and only
+// then sweeps up whatever pid is left outside that map.
+#[cfg(unix)]
+fn start_zombie_reaper(processes: Arc<Mutex<HashMap<String, Child>>>) {
+    thread::spawn(move || loop {
+        {
+            let mut processes = processes.lock().unwrap();
+            for child in processes.values_mut() {
+                let _ = child.try_wait();
+            }
+        }
+
+        loop {
+            let pid = unsafe { libc::waitpid(-1, std::ptr::null_mut(), libc::WNOHANG) };
+            if pid <= 0 {
+                break;
+            }
+            println!("🧟 Reaped exited child PID {}", pid);
+        }
+        thread::sleep(Duration::from_secs(2));
+    });
+}
+
+/// One spawned service recorded in the PID manifest, so a later launch can
+/// tell whether a previous run's child is still alive and holding its port.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct ServiceManifestEntry {
+    pid: u32,
+    script_path: String,
+    port: u16,
+}
+
+fn manifest_path(resource_dir: &Path) -> PathBuf {
+    resource_dir.join("hearthlink_data").join("services.pid.json")
+}
+
+fn read_manifest(resource_dir: &Path) -> HashMap<String, ServiceManifestEntry> {
+    std::fs::read_to_string(manifest_path(resource_dir))
+        .ok()
+        .and_then(|contents| serde_json::from_str(&contents).ok())
+        .unwrap_or_default()
+}
+
+fn write_manifest(resource_dir: &Path, manifest: &HashMap<String, ServiceManifestEntry>) {
+    let path = manifest_path(resource_dir);
+    if let Some(parent) = path.parent() {
+        let _ = std::fs::create_dir_all(parent);
+    }
+    if let Ok(contents) = serde_json::to_string_pretty(manifest) {
+        let _ = std::fs::write(path, contents);
+    }
+}
+
+fn record_service_pid(resource_dir: &Path, name: &str, pid: u32, script_path: &str, port: u16) {
+    let mut manifest = read_manifest(resource_dir);
+    manifest.insert(
+        name.to_string(),
+        ServiceManifestEntry { pid, script_path: script_path.to_string(), port },
+    );
+    write_manifest(resource_dir, &manifest);
+}
+
+fn forget_service_pid(resource_dir: &Path, name: &str) {
+    let mut manifest = read_manifest(resource_dir);
+    if manifest.remove(name).is_some() {
+        write_manifest(resource_dir, &manifest);
+    }
+}
+
+/// If a previous run crashed without reaching the window-close cleanup, its
+/// Python children can outlive it and keep holding their ports. `std`
+/// doesn't let us reattach a `Child` to an arbitrary PID, so rather than
+/// "adopting" these orphans into `processes`, we verify each manifest PID is
+/// still the same process (by command line) and terminate it before this
+/// run spawns a fresh copy.
+fn reconcile_orphaned_services(resource_dir: &Path) {
+    let manifest = read_manifest(resource_dir);
+    if manifest.is_empty() {
+        return;
+    }
+
+    let mut sys = sysinfo::System::new_all();
+    sys.refresh_processes();
+
+    for (name, entry) in &manifest {
+        let Some(process) = sys.process(sysinfo::Pid::from_u32(entry.pid)) else {
+            continue;
+        };
+
+        let cmd = process.cmd().join(" ");
+        if !cmd.contains(&entry.script_path) {
+            // PID was recycled for an unrelated process; nothing to clean up.
+            continue;
+        }
+
+        println!(
+            "🧹 Reclaiming orphaned {} service (PID {}) from a previous run",
+            name, entry.pid
+        );
+        if !process.kill_with(sysinfo::Signal::Term).unwrap_or(false) {
+            process.kill();
+        }
+
+        // The pre-flight port check runs right after this function returns,
+        // so wait for the kill to actually take effect (and the port to be
+        // released) instead of returning immediately and racing it.
+        let pid = sysinfo::Pid::from_u32(entry.pid);
+        let deadline = Instant::now() + Duration::from_secs(5);
+        loop {
+            sys.refresh_process(pid);
+            if sys.process(pid).is_none() {
+                break;
+            }
+            if Instant::now() >= deadline {
+                eprintln!(
+                    "⚠️ {} service (PID {}) still alive 5s after being reclaimed",
+                    name, entry.pid
+                );
+                break;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+    }
+
+    // The manifest is rewritten as each service is respawned; clear it here
+    // so a service whose start fails doesn't leave a stale entry behind.
+    write_manifest(resource_dir, &HashMap::new());
+}
+
+/// Drain a child's stdout/stderr stream line-by-line on a dedicated
+/// thread, tagging each line with the service name and level, so the pipe
+/// never backs up and blocks the child. Each line is appended to the
+/// service's rolling ring buffer and its on-disk log file, and pushed as a
+/// `service-log://{name}` event to any subscriber from `start_service_logs`.
+fn spawn_log_drainer(
+    name: String,
+    level: &'static str,
+    stream: impl Read + Send + 'static,
+    logs: Arc<Mutex<HashMap<String, VecDeque<String>>>>,
+    log_file_path: PathBuf,
+    app_handle: Arc<Mutex<Option<tauri::AppHandle>>>,
+    log_subscribers: Arc<Mutex<std::collections::HashSet<String>>>,
+) {
+    thread::spawn(move || {
+        let mut log_file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&log_file_path)
+            .ok();
+
+        for line in BufReader::new(stream).lines() {
+            let Ok(line) = line else { break };
+            let tagged = format!("[{}] {}: {}", level, name, line);
+
+            if let Some(file) = log_file.as_mut() {
+                let _ = writeln!(file, "{}", tagged);
+            }
+
+            {
+                let mut logs = logs.lock().unwrap();
+                let buffer = logs.entry(name.clone()).or_insert_with(VecDeque::new);
+                buffer.push_back(tagged.clone());
+                if buffer.len() > SERVICE_LOG_RING_CAPACITY {
+                    buffer.pop_front();
+                }
+            }
+
+            if log_subscribers.lock().unwrap().contains(&name) {
+                if let Some(handle) = app_handle.lock().unwrap().as_ref() {
+                    let _ = handle.emit_all(&format!("service-log://{}", name), &tagged);
+                }
+            }
+        }
+    });
+}
+
+// Service startup order: `core` waits on `vault`, `synapse` waits on
+// `core`. `start_all_services` groups these into runlevel tiers and
+// shutdown walks the same graph in reverse, instead of a hardcoded list.
+const SERVICE_DEPENDENCIES: &[(&str, &[&str])] = &[
+    ("alden", &[]),
+    ("vault", &[]),
+    ("core", &["vault"]),
+    ("synapse", &["core"]),
+];
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs()
+}
+
+/// Services named in `HEARTHLINK_ON_DEMAND_SERVICES` (comma-separated)
+/// are left un-spawned at startup and only started the first time
+/// something calls `ensure_service_running`, then stopped again after
+/// `HEARTHLINK_IDLE_TIMEOUT_SECS` of inactivity.
+fn on_demand_services_from_env() -> std::collections::HashSet<String> {
+    env::var("HEARTHLINK_ON_DEMAND_SERVICES")
+        .unwrap_or_default()
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+fn idle_timeout_from_env() -> Duration {
+    let secs = env::var("HEARTHLINK_IDLE_TIMEOUT_SECS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(300);
+    Duration::from_secs(secs)
+}
+
+/// How many consecutive crashes `restart_service_with_backoff` tolerates
+/// before giving up on a service, overridable via `HEARTHLINK_MAX_RESTART_ATTEMPTS`.
+fn max_restart_attempts_from_env() -> u32 {
+    env::var("HEARTHLINK_MAX_RESTART_ATTEMPTS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(5)
+}
+
+/// A way to ask "is this service okay?" `check_sync`/`check_async` return
+/// `Ok(())` for healthy, `Err(reason)` for unhealthy, so callers can store
+/// `reason` directly as `ServiceStatus.error_message`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum HealthProbe {
+    /// GET `url`; healthy when the response status equals `expect_status`.
+    Http { url: String, expect_status: u16 },
+    /// Run `command` with `args`; healthy when it exits with `success_exit_code`.
+    /// Lets a service (e.g. vault) report health via custom logic, such as an
+    /// unlock check, instead of a bare HTTP 200.
+    Exec {
+        command: String,
+        args: Vec<String>,
+        success_exit_code: i32,
+    },
+    /// Healthy as soon as something accepts connections on `port`.
+    Tcp { port: u16 },
+}
+
+impl HealthProbe {
+    fn http_with_path(port: u16, path: &str) -> Self {
+        HealthProbe::Http {
+            url: format!("http://127.0.0.1:{}{}", port, path),
+            expect_status: 200,
+        }
+    }
+
+    /// Short human-readable form, stored in `ServiceStatus.health_check_url`
+    /// for display purposes.
+    fn describe(&self) -> String {
+        match self {
+            HealthProbe::Http { url, .. } => url.clone(),
+            HealthProbe::Exec { command, .. } => format!("exec:{}", command),
+            HealthProbe::Tcp { port } => format!("tcp://127.0.0.1:{}", port),
+        }
+    }
+
+    /// Blocking probe, for use outside the Tokio runtime (`wait_for_tier_ready`).
+    fn check_sync(&self, client: &reqwest::blocking::Client) -> Result<(), String> {
+        match self {
+            HealthProbe::Http { url, expect_status } => {
+                let resp = client.get(url).send().map_err(|e| e.to_string())?;
+                if resp.status().as_u16() == *expect_status {
+                    Ok(())
+                } else {
+                    Err(format!("HTTP {}", resp.status()))
+                }
+            }
+            HealthProbe::Exec { command, args, success_exit_code } => {
+                let output = Command::new(command).args(args).output().map_err(|e| e.to_string())?;
+                match output.status.code() {
+                    Some(code) if code == *success_exit_code => Ok(()),
+                    Some(code) => Err(format!("exec probe exited {}", code)),
+                    None => Err("exec probe terminated by signal".to_string()),
+                }
+            }
+            HealthProbe::Tcp { port } => std::net::TcpStream::connect(("127.0.0.1", *port))
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+        }
+    }
+
+    /// Async probe, for use inside the Tokio runtime (`start_health_monitoring`).
+    /// `Exec`/`Tcp` run on a blocking thread so a slow probe can't stall the
+    /// monitor loop.
+    async fn check_async(&self, client: &reqwest::Client) -> Result<(), String> {
+        match self {
+            HealthProbe::Http { url, expect_status } => {
+                let resp = client.get(url).send().await.map_err(|e| e.to_string())?;
+                if resp.status().as_u16() == *expect_status {
+                    Ok(())
+                } else {
+                    Err(format!("HTTP {}", resp.status()))
+                }
+            }
+            HealthProbe::Exec { command, args, success_exit_code } => {
+                let command = command.clone();
+                let args = args.clone();
+                let success_exit_code = *success_exit_code;
+                tokio::task::spawn_blocking(move || {
+                    let output = Command::new(&command).args(&args).output().map_err(|e| e.to_string())?;
+                    match output.status.code() {
+                        Some(code) if code == success_exit_code => Ok(()),
+                        Some(code) => Err(format!("exec probe exited {}", code)),
+                        None => Err("exec probe terminated by signal".to_string()),
+                    }
+                })
+                .await
+                .map_err(|e| e.to_string())?
+            }
+            HealthProbe::Tcp { port } => {
+                let port = *port;
+                tokio::task::spawn_blocking(move || {
+                    std::net::TcpStream::connect(("127.0.0.1", port))
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                })
+                .await
+                .map_err(|e| e.to_string())?
+            }
+        }
+    }
+}
+
+/// Script path, port, and health probes for a named service under the
+/// current port profile — the single source of truth shared by startup,
+/// restart, and the manual `restart_service` command. `readiness` gates the
+/// startup barrier (is it actually serving?); `liveness` gates the
+/// auto-restart monitor (is it still a going concern?). Most services probe
+/// the same HTTP endpoint for both today.
+struct ServiceDescriptor {
+    script_path: String,
+    port: u16,
+    readiness: HealthProbe,
+    liveness: HealthProbe,
+}
+
+/// A service entry in the dynamic registry: enough to start, stop, and
+/// health-check a backend without baking its name into a match arm. The
+/// four built-in backends seed `ServiceManager`'s registry at construction;
+/// `services.toml` in the resource dir and the `register_service` command
+/// both add to it from there.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServiceRegistryEntry {
+    pub name: String,
+    pub script_path: String,
+    /// One of the built-in port-profile keys ("core"/"vault"/"synapse"/
+    /// "alden"), or a literal port number for a registered service that
+    /// isn't part of the port profile.
+    pub port_key: String,
+    /// Gates the startup barrier — is it actually serving yet?
+    #[serde(default = "default_probe_config")]
+    pub readiness: ProbeConfig,
+    /// Gates the auto-restart monitor — is it still a going concern? Lets a
+    /// service like vault declare an `Exec` unlock check here while still
+    /// readiness-gating on a plain HTTP health endpoint, or vice versa.
+    #[serde(default = "default_probe_config")]
+    pub liveness: ProbeConfig,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+}
+
+/// Declarative probe config carried in the registry, resolved to a concrete
+/// `HealthProbe` once the service's port is known from the port profile.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ProbeConfig {
+    Http { path: String },
+    Exec { command: String, args: Vec<String>, success_exit_code: i32 },
+    Tcp,
+}
+
+impl ProbeConfig {
+    fn resolve(&self, port: u16) -> HealthProbe {
+        match self {
+            ProbeConfig::Http { path } => HealthProbe::http_with_path(port, path),
+            ProbeConfig::Exec { command, args, success_exit_code } => HealthProbe::Exec {
+                command: command.clone(),
+                args: args.clone(),
+                success_exit_code: *success_exit_code,
+            },
+            ProbeConfig::Tcp => HealthProbe::Tcp { port },
+        }
+    }
+}
+
+fn default_health_path() -> String {
+    "/health".to_string()
+}
+
+fn default_probe_config() -> ProbeConfig {
+    ProbeConfig::Http { path: default_health_path() }
+}
+
+fn builtin_service_registry() -> Vec<ServiceRegistryEntry> {
+    let builtin = [
+        ("alden", "src/api/alden_api.py", "alden"),
+        ("vault", "src/vault/vault_api_server.py", "vault"),
+        ("core", "src/api/core_api.py", "core"),
+        ("synapse", "src/api/synapse_api_server.py", "synapse"),
+    ];
+    builtin
+        .into_iter()
+        .map(|(name, script_path, port_key)| ServiceRegistryEntry {
+            name: name.to_string(),
+            script_path: script_path.to_string(),
+            port_key: port_key.to_string(),
+            readiness: default_probe_config(),
+            liveness: default_probe_config(),
+            env: HashMap::new(),
+            args: Vec::new(),
+        })
+        .collect()
+}
+
+fn registry_file_path(resource_dir: &Path) -> PathBuf {
+    resource_dir.join("services.toml")
+}
+
+/// Group `SERVICE_DEPENDENCIES` into ordered tiers via a Kahn's-algorithm
+/// topological sort: tier 0 has no dependencies, tier 1 depends only on
+/// tier 0, and so on. A dependency cycle (which shouldn't happen with a
+/// hand-written graph) is broken by dumping whatever is left into a final
+/// tier rather than looping forever.
+fn compute_startup_tiers() -> Vec<Vec<&'static str>> {
+    let deps: HashMap<&str, &[&str]> = SERVICE_DEPENDENCIES.iter().copied().collect();
+    let mut remaining: Vec<&str> = deps.keys().copied().collect();
+    remaining.sort_unstable();
+    let mut started: std::collections::HashSet<&str> = std::collections::HashSet::new();
+    let mut tiers = Vec::new();
+
+    while !remaining.is_empty() {
+        let (ready, not_ready): (Vec<&str>, Vec<&str>) = remaining
+            .iter()
+            .copied()
+            .partition(|name| deps[name].iter().all(|dep| started.contains(dep)));
+
+        if ready.is_empty() {
+            tiers.push(not_ready);
+            break;
+        }
+
+        started.extend(&ready);
+        tiers.push(ready);
+        remaining = not_ready;
+    }
+
+    tiers
+}
+
+/// Shutdown order: the reverse of the startup tiers, flattened.
+fn compute_shutdown_order() -> Vec<&'static str> {
+    compute_startup_tiers().into_iter().rev().flatten().collect()
 }
 
 impl ServiceManager {
@@ -155,43 +949,380 @@ impl ServiceManager {
         Self {
             processes: Arc::new(Mutex::new(HashMap::new())),
             services: Arc::new(Mutex::new(HashMap::new())),
+            logs: Arc::new(Mutex::new(HashMap::new())),
+            listeners: Arc::new(Mutex::new(HashMap::new())),
+            on_demand_services: Arc::new(Mutex::new(on_demand_services_from_env())),
+            log_subscribers: Arc::new(Mutex::new(std::collections::HashSet::new())),
+            crash_records: Arc::new(Mutex::new(HashMap::new())),
+            app_handle: Arc::new(Mutex::new(None)),
             startup_time: SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .unwrap()
                 .as_secs(),
-            port_profile,
+            port_profile: Arc::new(Mutex::new(port_profile)),
             shutdown_in_progress: Arc::new(Mutex::new(false)),
+            service_registry: Arc::new(Mutex::new(builtin_service_registry())),
+            max_restart_attempts: max_restart_attempts_from_env(),
+        }
+    }
+
+    /// Merge any services declared in `services.toml` (resource dir) into
+    /// the registry, seeded with the four built-ins. An entry whose name
+    /// matches an existing one (e.g. re-declaring "core") replaces it;
+    /// everything else is additive, so this never removes a service that
+    /// `ServiceManager::new` already knows about.
+    pub fn load_registry_file(&self, resource_dir: &Path) {
+        let path = registry_file_path(resource_dir);
+        let Ok(contents) = std::fs::read_to_string(&path) else {
+            return;
+        };
+
+        #[derive(Deserialize)]
+        struct ServicesFile {
+            #[serde(default)]
+            service: Vec<ServiceRegistryEntry>,
+        }
+
+        match toml::from_str::<ServicesFile>(&contents) {
+            Ok(file) => {
+                let mut registry = self.service_registry.lock().unwrap();
+                for entry in file.service {
+                    match registry.iter_mut().find(|e| e.name == entry.name) {
+                        Some(existing) => *existing = entry,
+                        None => registry.push(entry),
+                    }
+                }
+                println!("📖 Loaded service registry from {} ({} service(s) known)", path.display(), registry.len());
+            }
+            Err(e) => eprintln!("⚠️ failed to parse {}: {}", path.display(), e),
+        }
+    }
+
+    /// Register (or replace) a service at runtime, e.g. from a settings UI,
+    /// without needing a `services.toml` edit or a rebuild.
+    pub fn register_service(&self, entry: ServiceRegistryEntry) {
+        let mut registry = self.service_registry.lock().unwrap();
+        match registry.iter_mut().find(|e| e.name == entry.name) {
+            Some(existing) => *existing = entry,
+            None => registry.push(entry),
+        }
+    }
+
+    /// Drop a service from the registry. Does not stop it if currently
+    /// running.
+    pub fn unregister_service(&self, name: &str) -> bool {
+        let mut registry = self.service_registry.lock().unwrap();
+        let before = registry.len();
+        registry.retain(|e| e.name != name);
+        registry.len() != before
+    }
+
+    pub fn list_services(&self) -> Vec<ServiceRegistryEntry> {
+        self.service_registry.lock().unwrap().clone()
+    }
+
+    /// Script path, port, and health probes for a registered service under
+    /// the current port profile — the single source of truth shared by
+    /// startup, restart, and the manual `restart_service` command.
+    fn service_descriptor(&self, name: &str) -> Option<ServiceDescriptor> {
+        let registry = self.service_registry.lock().unwrap();
+        let entry = registry.iter().find(|e| e.name == name)?;
+        let port = self.current_port_profile().resolve_port(&entry.port_key)?;
+        Some(ServiceDescriptor {
+            script_path: entry.script_path.clone(),
+            port,
+            readiness: entry.readiness.resolve(port),
+            liveness: entry.liveness.resolve(port),
+        })
+    }
+
+    /// Snapshot of the currently active port profile. Cloned out rather than
+    /// handed back as a guard so callers can pass it straight to
+    /// `service_descriptor` without holding the lock.
+    pub fn current_port_profile(&self) -> PortProfile {
+        self.port_profile.lock().unwrap().clone()
+    }
+
+    /// Called once from `main`'s `.setup()` so background tasks (log
+    /// streaming, crash/state-change events) can emit to the webview.
+    pub fn set_app_handle(&self, handle: tauri::AppHandle) {
+        *self.app_handle.lock().unwrap() = Some(handle);
+    }
+
+    /// Broadcast a service's lifecycle transition to the webview.
+    fn emit_state_changed(&self, name: &str, state: ServiceLifecycleState) {
+        if let Some(handle) = self.app_handle.lock().unwrap().as_ref() {
+            let _ = handle.emit_all(
+                "service-state-changed",
+                ServiceStateChangedEvent { name: name.to_string(), state },
+            );
+        }
+    }
+
+    /// Append a crash/restart entry to `name`'s history, trimming to
+    /// `CRASH_HISTORY_CAPACITY` like the log ring buffer.
+    fn record_crash(&self, name: &str, exit_status: Option<i32>, restart_count: u32, last_health_error: Option<String>) {
+        let mut crash_records = self.crash_records.lock().unwrap();
+        let history = crash_records.entry(name.to_string()).or_insert_with(Vec::new);
+        history.push(CrashRecord {
+            timestamp: now_secs(),
+            exit_status,
+            restart_count,
+            last_health_error,
+        });
+        if history.len() > CRASH_HISTORY_CAPACITY {
+            history.remove(0);
         }
     }
 
     pub fn start_all_services(&self, resource_dir: PathBuf) -> Result<(), String> {
-        // Get ports from the current profile
-        let (core_port, vault_port, synapse_port, alden_port) = self.port_profile.get_ports();
-        
-        println!("📡 Starting services on ports - Core:{}, Vault:{}, Synapse:{}, Alden:{}", 
+        let (core_port, vault_port, synapse_port, alden_port) = self.current_port_profile().get_ports();
+        println!("📡 Starting services on ports - Core:{}, Vault:{}, Synapse:{}, Alden:{}",
                 core_port, vault_port, synapse_port, alden_port);
-        
-        // Define Python services with their configurations using dynamic ports
-        let services = vec![
-            ("alden", "src/api/alden_api.py", alden_port, format!("http://127.0.0.1:{}/health", alden_port)),
-            ("vault", "src/vault/vault_api_server.py", vault_port, format!("http://127.0.0.1:{}/health", vault_port)),
-            ("core", "src/api/core_api.py", core_port, format!("http://127.0.0.1:{}/health", core_port)),
-            ("synapse", "src/api/synapse_api_server.py", synapse_port, format!("http://127.0.0.1:{}/health", synapse_port)),
-        ];
-        
+
+        // Pick up any services declared outside the built-ins before we
+        // compute ports/tiers, so a freshly-registered service starts on
+        // this very launch rather than needing a restart.
+        self.load_registry_file(&resource_dir);
+
+        let tiers = compute_startup_tiers();
+        println!("📶 Startup tiers: {:?}", tiers);
+
+        let services: Vec<(&str, u16)> = tiers
+            .iter()
+            .flatten()
+            .map(|name| {
+                let descriptor = self.service_descriptor(name)
+                    .unwrap_or_else(|| panic!("no service descriptor for {}", name));
+                (*name, descriptor.port)
+            })
+            .collect();
+
+        // Reclaim any services left running by a previous instance that
+        // crashed before it could clean up, so they don't hold ports we're
+        // about to bind.
+        reconcile_orphaned_services(&resource_dir);
+
         // Pre-flight port availability check
         self.check_port_availability(&services)?;
 
-        for (name, script_path, port, health_url) in services {
-            self.start_service(name, script_path, port, &health_url, &resource_dir)?;
+        let mut started: Vec<&str> = Vec::new();
+
+        for tier in &tiers {
+            let mut tier_started: Vec<&str> = Vec::new();
+
+            for name in tier {
+                let descriptor = self.service_descriptor(name)
+                    .ok_or_else(|| format!("no service descriptor for {}", name))?;
+
+                if self.on_demand_services.lock().unwrap().contains(*name) {
+                    println!("💤 {} service is on-demand, leaving un-spawned until first use", name);
+                    self.register_stopped_service(name, descriptor.port, &descriptor.readiness.describe());
+                    continue;
+                }
+
+                self.start_service(name, &descriptor.script_path, descriptor.port, &descriptor.readiness.describe(), &resource_dir)?;
+                started.push(name);
+                tier_started.push(name);
+            }
+
+            // Block until every non-on-demand service in this tier reports
+            // healthy before starting services that depend on it.
+            if let Err(e) = self.wait_for_tier_ready(&tier_started, Duration::from_secs(30)) {
+                eprintln!("🚫 Startup tier {:?} never became healthy: {}", tier, e);
+                for name in started.iter().rev() {
+                    self.graceful_stop_service_enhanced(name);
+                }
+                return Err(format!("Startup aborted: {}", e));
+            }
+        }
+
+        // Services registered beyond the four built-ins (via `services.toml`
+        // or `register_service`) aren't part of `SERVICE_DEPENDENCIES`, so
+        // they have no declared tier — start them last, independently of
+        // each other, once every built-in dependency is already up.
+        let extra_names: Vec<String> = self
+            .service_registry
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|e| e.name.clone())
+            .filter(|name| !SERVICE_DEPENDENCIES.iter().any(|(n, _)| n == name))
+            .collect();
+
+        for name in &extra_names {
+            let descriptor = match self.service_descriptor(name) {
+                Some(d) => d,
+                None => continue,
+            };
+
+            if self.on_demand_services.lock().unwrap().contains(name.as_str()) {
+                println!("💤 {} service is on-demand, leaving un-spawned until first use", name);
+                self.register_stopped_service(name, descriptor.port, &descriptor.readiness.describe());
+                continue;
+            }
+
+            if let Err(e) = self.start_service(name, &descriptor.script_path, descriptor.port, &descriptor.readiness.describe(), &resource_dir) {
+                eprintln!("🚫 failed to start registered service {}: {}", name, e);
+            }
+        }
+
+        // Idle-reap on-demand services that go unused; they're respawned
+        // transparently by `ensure_service_running` the next time they're needed.
+        if !self.on_demand_services.lock().unwrap().is_empty() {
+            self.start_idle_reaper(idle_timeout_from_env());
         }
 
         // Start health monitoring
-        self.start_health_monitoring();
+        self.start_health_monitoring(resource_dir);
+
+        Ok(())
+    }
+
+    /// Register a `ServiceStatus` for an on-demand service that hasn't
+    /// been spawned yet, so `get_system_health` and `ensure_service_running`
+    /// have something to look at before the first on-demand start.
+    fn register_stopped_service(&self, name: &str, port: u16, health_url: &str) {
+        let mut services = self.services.lock().unwrap();
+        services.insert(
+            name.to_string(),
+            ServiceStatus {
+                name: name.to_string(),
+                status: "stopped".to_string(),
+                port,
+                pid: None,
+                started_at: None,
+                health_check_url: health_url.to_string(),
+                last_health_check: None,
+                error_message: None,
+                restart_count: 0,
+                last_restart: None,
+                restart_backoff: 1,
+                consecutive_failures: 0,
+                last_active: None,
+            },
+        );
+    }
+
+    /// Start `name` if it isn't already running and record that it was
+    /// just needed. Called by Tauri commands and the `hearthlink://` proxy
+    /// on behalf of anything wanting to use an on-demand service.
+    pub fn ensure_service_running(&self, name: &str, resource_dir: &PathBuf) -> Result<(), String> {
+        let is_running = {
+            let mut services = self.services.lock().unwrap();
+            match services.get_mut(name) {
+                Some(service) => {
+                    service.last_active = Some(now_secs());
+                    service.status == "running" || service.status == "starting"
+                }
+                None => return Err(format!("Unknown service: {}", name)),
+            }
+        };
+
+        if is_running {
+            return Ok(());
+        }
+
+        // The previous instance's socket-activated listener may be stale
+        // (e.g. the child crashed mid-handshake); drop it so respawning
+        // binds a fresh one instead of inheriting a wedged accept queue.
+        self.listeners.lock().unwrap().remove(name);
+
+        let descriptor = self.service_descriptor(name)
+            .ok_or_else(|| format!("Unknown service: {}", name))?;
+        println!("⚡ On-demand start: {} service", name);
+        self.start_service(name, &descriptor.script_path, descriptor.port, &descriptor.readiness.describe(), resource_dir)
+    }
+
+    /// Stop any on-demand service that's been idle past `idle_timeout`.
+    /// Re-`start_service` happens transparently on its next use via
+    /// `ensure_service_running`.
+    fn start_idle_reaper(&self, idle_timeout: Duration) {
+        let on_demand = Arc::clone(&self.on_demand_services);
+        let manager = self.clone();
+
+        tokio::spawn(async move {
+            loop {
+                sleep(Duration::from_secs(30)).await;
+
+                let names: Vec<String> = on_demand.lock().unwrap().iter().cloned().collect();
+                for name in names {
+                    let idle = {
+                        let services = manager.services.lock().unwrap();
+                        services.get(&name).map_or(false, |s| {
+                            s.status == "running"
+                                && s.last_active
+                                    .map(|t| now_secs().saturating_sub(t) >= idle_timeout.as_secs())
+                                    .unwrap_or(false)
+                        })
+                    };
+
+                    if idle {
+                        println!("💤 {} service idle for {}s+, stopping", name, idle_timeout.as_secs());
+                        manager.graceful_stop_service_enhanced(&name);
+                    }
+                }
+            }
+        });
+    }
+
+    /// Poll each service in `tier` until its readiness probe passes, or
+    /// `timeout` elapses.
+    fn wait_for_tier_ready(&self, tier: &[&str], timeout: Duration) -> Result<(), String> {
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .build()
+            .map_err(|e| e.to_string())?;
+
+        let deadline = std::time::Instant::now() + timeout;
+        let mut pending: Vec<&str> = tier.to_vec();
+
+        while !pending.is_empty() {
+            pending.retain(|name| match self.service_descriptor(name) {
+                Some(descriptor) => descriptor.readiness.check_sync(&client).is_err(),
+                None => true,
+            });
+
+            if pending.is_empty() {
+                break;
+            }
+
+            if std::time::Instant::now() >= deadline {
+                return Err(format!("timed out waiting for {:?} to become healthy", pending));
+            }
+
+            thread::sleep(Duration::from_millis(500));
+        }
 
         Ok(())
     }
 
+    // Unix only: bind (or reuse) the listening socket for `name`/`port` and
+    // clear its close-on-exec flag so it survives into the child via
+    // `LISTEN_FDS`. Reusing an already-bound listener across restarts is
+    // what keeps the accept queue open through `restart_service_with_backoff`.
+    #[cfg(unix)]
+    fn ensure_listener(&self, name: &str, port: u16) -> Result<std::os::unix::io::RawFd, String> {
+        use std::os::unix::io::AsRawFd;
+
+        let mut listeners = self.listeners.lock().unwrap();
+        // A listener bound to a different port (e.g. after SIGHUP switched
+        // the port profile) is replaced rather than reused.
+        if listeners.get(name).map_or(false, |l| l.local_addr().map_or(true, |a| a.port() != port)) {
+            listeners.remove(name);
+        }
+        if !listeners.contains_key(name) {
+            let listener = std::net::TcpListener::bind(("127.0.0.1", port))
+                .map_err(|e| format!("failed to pre-bind socket for {} service: {}", name, e))?;
+            listeners.insert(name.to_string(), listener);
+        }
+
+        let fd = listeners[name].as_raw_fd();
+        socket_activation::clear_cloexec(fd)
+            .map_err(|e| format!("failed to prepare inherited socket for {} service: {}", name, e))?;
+        Ok(fd)
+    }
+
     fn start_service(
         &self,
         name: &str,
@@ -209,9 +1340,10 @@ impl ServiceManager {
 
         // Set environment variables
         let vault_key = env::var("HEARTHLINK_VAULT_KEY")
-            .unwrap_or_else(|_| "yFLl9T3j6l_rsrgSIHMDqr5O_vt62MdpkJuhIEuilAM=".to_string());
+            .unwrap_or_else(|_| DEFAULT_VAULT_KEY.to_string());
 
-        let mut cmd = Command::new(&python_path)
+        let mut command = Command::new(&python_path);
+        command
             .arg(full_script_path)
             .arg("--host")
             .arg("127.0.0.1")
@@ -222,12 +1354,73 @@ impl ServiceManager {
             .env("HEARTHLINK_DATA_DIR", resource_dir.join("hearthlink_data").display().to_string())
             .stdin(Stdio::null())
             .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        // Extra args and env declared for this service in the registry.
+        if let Some(entry) = self.service_registry.lock().unwrap().iter().find(|e| e.name == name) {
+            command.args(&entry.args).envs(&entry.env);
+        }
+
+        // Hand our pre-bound listening socket to the child so the Rust
+        // side keeps owning it across restarts (see `ensure_listener`).
+        // Windows has no fd-inheritance convention here, so it falls back
+        // to the child binding `--port` itself, as before.
+        #[cfg(unix)]
+        {
+            use std::os::unix::process::CommandExt;
+
+            let fd = self.ensure_listener(name, port)?;
+            command.env("LISTEN_FDS", "1");
+            unsafe {
+                command.pre_exec(move || {
+                    // Runs post-fork, pre-exec, in the child: move the
+                    // inherited socket to the fd systemd's sd_listen_fds()
+                    // convention expects, then stamp LISTEN_PID with our
+                    // own (now-child) pid so the child's check passes.
+                    if libc::dup2(fd, socket_activation::LISTEN_FDS_START) < 0 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                    std::env::set_var("LISTEN_PID", std::process::id().to_string());
+                    Ok(())
+                });
+            }
+        }
+
+        let mut cmd = command
             .spawn()
             .map_err(|e| format!("Failed to start {} service: {}", name, e))?;
 
         let pid = cmd.id();
 
+        // Drain stdout/stderr on dedicated threads so the OS pipe buffer
+        // never fills up and blocks the child (see `spawn_log_drainer`).
+        let logs_dir = resource_dir.join("hearthlink_data").join("logs");
+        let _ = std::fs::create_dir_all(&logs_dir);
+        let log_file_path = logs_dir.join(format!("{}.log", name));
+
+        if let Some(stdout) = cmd.stdout.take() {
+            spawn_log_drainer(
+                name.to_string(),
+                "info",
+                stdout,
+                Arc::clone(&self.logs),
+                log_file_path.clone(),
+                Arc::clone(&self.app_handle),
+                Arc::clone(&self.log_subscribers),
+            );
+        }
+        if let Some(stderr) = cmd.stderr.take() {
+            spawn_log_drainer(
+                name.to_string(),
+                "warn",
+                stderr,
+                Arc::clone(&self.logs),
+                log_file_path,
+                Arc::clone(&self.app_handle),
+                Arc::clone(&self.log_subscribers),
+            );
+        }
+
         // Store process
         {
             let mut processes = self.processes.lock().unwrap();
@@ -252,6 +1445,8 @@ impl ServiceManager {
             restart_count: 0,
             last_restart: None,
             restart_backoff: 1, // Start with 1 second backoff
+            consecutive_failures: 0,
+            last_active: Some(now_secs()),
         };
 
         {
@@ -259,6 +1454,8 @@ impl ServiceManager {
             services.insert(name.to_string(), status);
         }
 
+        record_service_pid(resource_dir, name, pid, script_path, port);
+
         println!("Started {} service on port {} with PID {}", name, port, pid);
         Ok(())
     }
@@ -282,10 +1479,10 @@ impl ServiceManager {
         Err("Python 3.x not found. Please ensure Python 3.x is installed and in PATH.".to_string())
     }
 
-    fn check_port_availability(&self, services: &[(&str, &str, u16, String)]) -> Result<(), String> {
+    fn check_port_availability(&self, services: &[(&str, u16)]) -> Result<(), String> {
         use std::net::TcpListener;
-        
-        for (name, _, port, _) in services {
+
+        for (name, port) in services {
             match TcpListener::bind(format!("127.0.0.1:{}", port)) {
                 Ok(_) => {
                     println!("✓ Port {} available for {} service", port, name);
@@ -299,22 +1496,16 @@ impl ServiceManager {
     }
     
     fn restart_service_with_backoff(&self, service_name: &str, resource_dir: &PathBuf) -> Result<(), String> {
-        let (core_port, vault_port, synapse_port, alden_port) = self.port_profile.get_ports();
-        
-        let service_config = match service_name {
-            "alden" => ("src/api/alden_api.py", alden_port, format!("http://127.0.0.1:{}/health", alden_port)),
-            "vault" => ("src/vault/vault_api_server.py", vault_port, format!("http://127.0.0.1:{}/health", vault_port)),
-            "core" => ("src/api/core_api.py", core_port, format!("http://127.0.0.1:{}/health", core_port)),
-            "synapse" => ("src/api/synapse_api_server.py", synapse_port, format!("http://127.0.0.1:{}/health", synapse_port)),
-            _ => return Err(format!("Unknown service: {}", service_name)),
-        };
-        
+        let descriptor = self.service_descriptor(service_name)
+            .ok_or_else(|| format!("Unknown service: {}", service_name))?;
+
         // Check if service should be restarted based on backoff
         let should_restart = {
             let services = self.services.lock().unwrap();
             if let Some(service) = services.get(service_name) {
-                if service.restart_count >= 5 {
+                if service.restart_count >= self.max_restart_attempts {
                     println!("Service {} has failed {} times, not restarting", service_name, service.restart_count);
+                    self.emit_state_changed(service_name, ServiceLifecycleState::Failed);
                     return Err("Max restart attempts exceeded".to_string());
                 }
                 
@@ -343,7 +1534,7 @@ impl ServiceManager {
         self.graceful_stop_service(service_name);
         
         // Start the service again
-        match self.start_service(service_name, service_config.0, service_config.1, &service_config.2, resource_dir) {
+        match self.start_service(service_name, &descriptor.script_path, descriptor.port, &descriptor.readiness.describe(), resource_dir) {
             Ok(_) => {
                 // Update restart statistics
                 let mut services = self.services.lock().unwrap();
@@ -391,9 +1582,14 @@ impl ServiceManager {
         }
     }
     
-    fn start_health_monitoring(&self) {
+    // A service must fail this many consecutive probes before the monitor
+    // restarts it, so a single transient 500/timeout doesn't bounce it.
+    const UNHEALTHY_FAILURE_THRESHOLD: u32 = 3;
+
+    fn start_health_monitoring(&self, resource_dir: PathBuf) {
         let services_clone = Arc::clone(&self.services);
-        
+        let manager_clone = self.clone();
+
         tokio::spawn(async move {
             let client = reqwest::Client::builder()
                 .timeout(Duration::from_secs(5))
@@ -408,7 +1604,7 @@ impl ServiceManager {
             loop {
                 let probe_interval = if startup_phase { 5 } else { 30 };
                 sleep(Duration::from_secs(probe_interval)).await;
-                
+
                 if startup_phase {
                     startup_elapsed += probe_interval;
                     if startup_elapsed >= startup_duration {
@@ -416,63 +1612,102 @@ impl ServiceManager {
                         println!("Health monitoring: Switching to steady-state mode (30s intervals)");
                     }
                 }
-                
+
+                // On-demand services sitting idle ("stopped") aren't probed;
+                // they only come back to life via `ensure_service_running`.
                 let service_names: Vec<String> = {
                     let services = services_clone.lock().unwrap();
-                    services.keys().cloned().collect()
+                    services
+                        .iter()
+                        .filter(|(_, s)| s.status != "stopped")
+                        .map(|(name, _)| name.clone())
+                        .collect()
                 };
 
                 for name in service_names {
-                    let health_url = {
-                        let services = services_clone.lock().unwrap();
-                        services.get(&name).map(|s| s.health_check_url.clone())
+                    let liveness = match manager_clone.service_descriptor(&name) {
+                        Some(descriptor) => descriptor.liveness,
+                        None => continue,
                     };
 
-                    if let Some(url) = health_url {
-                        let health_result = client.get(&url).send().await;
-                        
+                    let health_result = liveness.check_async(&client).await;
+
+                    let mut should_restart = false;
+                    let mut became_healthy = false;
+                    let mut became_unhealthy = false;
+                    let mut last_health_error = None;
+                    {
                         let mut services = services_clone.lock().unwrap();
                         if let Some(service) = services.get_mut(&name) {
-                            service.last_health_check = Some(
-                                SystemTime::now()
-                                    .duration_since(UNIX_EPOCH)
-                                    .unwrap()
-                                    .as_secs(),
-                            );
+                            service.last_health_check = Some(now_secs());
 
                             match health_result {
-                            Ok(response) if response.status().is_success() => {
-                            if service.status == "starting" || service.status == "error" {
-                            service.status = "running".to_string();
-                            service.error_message = None;
-                            // Reset restart count on successful health check
-                                service.restart_count = 0;
-                                    service.restart_backoff = 1;
-                                    println!("{} service is healthy", name);
-                            }
-                            }
-                            Ok(response) => {
-                                let was_running = service.status == "running";
-                            service.status = "error".to_string();
-                            service.error_message = Some(format!("HTTP {}", response.status()));
-                                
-                                    if was_running {
-                                        println!("{} service unhealthy (HTTP {}), scheduling restart", name, response.status());
+                                Ok(()) => {
+                                    service.consecutive_failures = 0;
+                                    if service.status == "starting" || service.status == "error" {
+                                        service.status = "running".to_string();
+                                        service.error_message = None;
+                                        // Reset restart count on successful health check
+                                        service.restart_count = 0;
+                                        service.restart_backoff = 1;
+                                        println!("{} service is healthy", name);
+                                        became_healthy = true;
                                     }
                                 }
-                                Err(e) => {
+                                Err(reason) => {
                                     let was_running = service.status == "running";
                                     service.status = "error".to_string();
-                                    service.error_message = Some(e.to_string());
-                                    
+                                    service.error_message = Some(reason.clone());
+                                    service.consecutive_failures += 1;
+                                    last_health_error = Some(reason.clone());
+
                                     if was_running {
-                                        println!("{} service unhealthy ({}), scheduling restart", name, e);
+                                        println!("{} service unhealthy ({})", name, reason);
+                                        became_unhealthy = true;
                                     }
                                 }
                             }
+
+                            if service.consecutive_failures >= Self::UNHEALTHY_FAILURE_THRESHOLD {
+                                should_restart = true;
+                                service.consecutive_failures = 0;
+                            }
+                        }
+                    }
+
+                    if became_healthy {
+                        manager_clone.emit_state_changed(&name, ServiceLifecycleState::Up);
+                    } else if became_unhealthy {
+                        manager_clone.emit_state_changed(&name, ServiceLifecycleState::Unhealthy);
+                    }
+
+                    if should_restart {
+                        println!(
+                            "{} service failed {} consecutive health checks, restarting",
+                            name, Self::UNHEALTHY_FAILURE_THRESHOLD
+                        );
+                        manager_clone.emit_state_changed(&name, ServiceLifecycleState::Restarting);
+
+                        let exit_status = {
+                            let mut processes = manager_clone.processes.lock().unwrap();
+                            processes.get_mut(&name).and_then(|p| p.try_wait().ok().flatten()).and_then(|s| s.code())
+                        };
+                        let restart_count = {
+                            let services = services_clone.lock().unwrap();
+                            services.get(&name).map(|s| s.restart_count).unwrap_or(0)
+                        };
+                        manager_clone.record_crash(&name, exit_status, restart_count, last_health_error);
+
+                        if let Err(e) = manager_clone.restart_service_with_backoff(&name, &resource_dir) {
+                            eprintln!("Failed to auto-restart {} service: {}", name, e);
                         }
                     }
                 }
+
+                // Keep the tray's status glyphs in sync with what we just polled.
+                if let Some(handle) = manager_clone.app_handle.lock().unwrap().as_ref() {
+                    tray::refresh(handle, &manager_clone);
+                }
             }
         });
     }
@@ -496,6 +1731,55 @@ impl ServiceManager {
         }
     }
 
+    /// Used by the SIGHUP handler and the tray's Restart All entry: re-read
+    /// the port profile and on-demand service list from the environment,
+    /// then restart already-running services tier by tier (waiting for
+    /// readiness between tiers), instead of tearing everything down via
+    /// `stop_all_services`.
+    pub fn rolling_restart(&self, resource_dir: PathBuf) {
+        println!("🔁 Reloading config and rolling-restarting services");
+
+        *self.port_profile.lock().unwrap() = PortProfile::from_env();
+        *self.on_demand_services.lock().unwrap() = on_demand_services_from_env();
+
+        for tier in &compute_startup_tiers() {
+            let mut tier_restarted: Vec<&str> = Vec::new();
+
+            for name in tier {
+                if self.on_demand_services.lock().unwrap().contains(*name) {
+                    continue;
+                }
+
+                let was_running = {
+                    let services = self.services.lock().unwrap();
+                    services.get(*name).map_or(false, |s| s.status != "stopped")
+                };
+                if !was_running {
+                    continue;
+                }
+
+                let descriptor = match self.service_descriptor(name) {
+                    Some(d) => d,
+                    None => continue,
+                };
+
+                println!("🔁 Rolling-restarting {} service", name);
+                self.graceful_stop_service_enhanced(name);
+                if let Err(e) = self.start_service(name, &descriptor.script_path, descriptor.port, &descriptor.readiness.describe(), &resource_dir) {
+                    eprintln!("🚫 Failed to restart {} service during reload: {}", name, e);
+                    continue;
+                }
+                tier_restarted.push(name);
+            }
+
+            if let Err(e) = self.wait_for_tier_ready(&tier_restarted, Duration::from_secs(30)) {
+                eprintln!("⚠️  Rolling restart: tier {:?} never became healthy again: {}", tier, e);
+            }
+        }
+
+        println!("✅ Rolling restart complete");
+    }
+
     pub fn stop_all_services(&self) {
         // Check if shutdown is already in progress
         {
@@ -510,7 +1794,7 @@ impl ServiceManager {
         println!("🛑 Initiating enhanced shutdown sequence...");
         
         // Step 1: Gracefully shutdown in reverse dependency order
-        let shutdown_order = vec!["synapse", "core", "vault", "alden"];
+        let shutdown_order = compute_shutdown_order();
         
         for service_name in &shutdown_order {
             self.graceful_stop_service_enhanced(service_name);
@@ -616,17 +1900,9 @@ async fn restart_service(
         .resource_dir()
         .ok_or("Failed to get resource directory")?;
 
-    // Get current port profile
-    let (core_port, vault_port, synapse_port, alden_port) = service_manager.port_profile.get_ports();
-    
     // Find service configuration
-    let service_config = match service_name.as_str() {
-        "alden" => ("src/api/alden_api.py", alden_port, format!("http://127.0.0.1:{}/health", alden_port)),
-        "vault" => ("src/vault/vault_api_server.py", vault_port, format!("http://127.0.0.1:{}/health", vault_port)),
-        "core" => ("src/api/core_api.py", core_port, format!("http://127.0.0.1:{}/health", core_port)),
-        "synapse" => ("src/api/synapse_api_server.py", synapse_port, format!("http://127.0.0.1:{}/health", synapse_port)),
-        _ => return Err(format!("Unknown service: {}", service_name)),
-    };
+    let descriptor = service_manager.service_descriptor(&service_name)
+        .ok_or_else(|| format!("Unknown service: {}", service_name))?;
 
     // Stop existing process if running
     {
@@ -640,9 +1916,9 @@ async fn restart_service(
     // Start the service again
     service_manager.start_service(
         &service_name,
-        service_config.0,
-        service_config.1,
-        &service_config.2,
+        &descriptor.script_path,
+        descriptor.port,
+        &descriptor.readiness.describe(),
         &resource_dir,
     )?;
 
@@ -650,10 +1926,93 @@ async fn restart_service(
 }
 
 #[tauri::command]
-async fn get_service_logs(service_name: String) -> Result<String, String> {
-    // In a production app, you'd read from log files
-    // For now, return a placeholder
-    Ok(format!("Logs for {} service would appear here", service_name))
+async fn ensure_service_running(
+    service_name: String,
+    service_manager: State<'_, ServiceManager>,
+    app: tauri::AppHandle,
+) -> Result<String, String> {
+    let resource_dir = app
+        .path_resolver()
+        .resource_dir()
+        .ok_or("Failed to get resource directory")?;
+
+    service_manager.ensure_service_running(&service_name, &resource_dir)?;
+
+    Ok(format!("{} service is running", service_name))
+}
+
+#[tauri::command]
+async fn get_service_logs(
+    service_name: String,
+    tail_lines: Option<usize>,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<Vec<String>, String> {
+    let logs = service_manager.logs.lock().unwrap();
+    Ok(logs
+        .get(&service_name)
+        .map(|buffer| {
+            let n = tail_lines.unwrap_or(buffer.len());
+            buffer.iter().rev().take(n).rev().cloned().collect()
+        })
+        .unwrap_or_default())
+}
+
+/// Start emitting `service-log://{service_name}` events for each new log
+/// line, instead of the frontend having to poll `get_service_logs`.
+#[tauri::command]
+async fn start_service_logs(
+    service_name: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<String, String> {
+    service_manager.log_subscribers.lock().unwrap().insert(service_name.clone());
+    Ok(format!("Streaming logs for {} service", service_name))
+}
+
+#[tauri::command]
+async fn stop_service_logs(
+    service_name: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<String, String> {
+    service_manager.log_subscribers.lock().unwrap().remove(&service_name);
+    Ok(format!("Stopped streaming logs for {} service", service_name))
+}
+
+#[tauri::command]
+async fn get_service_crash_history(
+    service_name: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<Vec<CrashRecord>, String> {
+    let crash_records = service_manager.crash_records.lock().unwrap();
+    Ok(crash_records.get(&service_name).cloned().unwrap_or_default())
+}
+
+#[tauri::command]
+async fn register_service(
+    entry: ServiceRegistryEntry,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<String, String> {
+    let name = entry.name.clone();
+    service_manager.register_service(entry);
+    Ok(format!("Registered {} service", name))
+}
+
+#[tauri::command]
+async fn unregister_service(
+    service_name: String,
+    service_manager: State<'_, ServiceManager>,
+) -> Result<String, String> {
+    if service_manager.unregister_service(&service_name) {
+        Ok(format!("Unregistered {} service", service_name))
+    } else {
+        Err(format!("No such registered service: {}", service_name))
+    }
+}
+
+#[tauri::command]
+async fn list_services(
+    service_manager: State<'_, ServiceManager>,
+) -> Result<Vec<ServiceRegistryEntry>, String> {
+    Ok(service_manager.list_services())
 }
 
 #[tauri::command]
@@ -678,20 +2037,43 @@ fn main() {
 
     tauri::Builder::default()
         .manage(service_manager)
+        .register_uri_scheme_protocol("hearthlink", proxy::handle)
+        .system_tray(tray::build())
+        .on_system_tray_event(|app, event| {
+            let resource_dir = app
+                .path_resolver()
+                .resource_dir()
+                .expect("Failed to get resource directory");
+            tray::handle_event(&app.handle(), event, &resource_dir);
+        })
         .invoke_handler(tauri::generate_handler![
             get_app_status,
             greet,
             get_system_health,
             restart_service,
+            ensure_service_running,
             get_service_logs,
+            start_service_logs,
+            stop_service_logs,
+            get_service_crash_history,
+            register_service,
+            unregister_service,
+            list_services,
             vault_rotation::rotate_vault_keys,
             vault_rotation::get_vault_key_status,
             vault_rotation::get_vault_rotation_history,
-            vault_rotation::rollback_vault_key
+            vault_rotation::rollback_vault_key,
+            vault_rotation::start_vault_rotation_worker,
+            vault_rotation::stop_vault_rotation_worker,
+            vault_rotation::verify_vault_key_integrity,
+            vault_rotation::record_vault_key_use,
+            vault_rotation::export_vault_backup,
+            vault_rotation::import_vault_backup
         ])
         .setup(|app| {
             let window = app.get_webview_window("main").unwrap();
             let service_manager = app.state::<ServiceManager>();
+            service_manager.set_app_handle(app.handle());
 
             // Get resource directory
             let resource_dir = app
@@ -701,6 +2083,54 @@ fn main() {
 
             println!("Resource directory: {}", resource_dir.display());
 
+            // Collect zombies from children that exit outside the managed
+            // stop/restart paths, and handle direct OS signals the same way
+            // the window-close path does.
+            #[cfg(unix)]
+            {
+                start_zombie_reaper(Arc::clone(&service_manager.processes));
+                signals::install(service_manager.inner().clone(), resource_dir.clone());
+            }
+
+            // Seed the tray with the un-spawned ("stopped") status of every
+            // registered service before the first service even starts.
+            tray::refresh(&app.handle(), &service_manager);
+
+            // Global shortcuts: one to bring the window back, one to nudge
+            // any currently-unhealthy service into a restart.
+            {
+                let show_handle = app.handle();
+                let restart_handle = app.handle();
+                let mut shortcuts = app.global_shortcut_manager();
+                let _ = shortcuts.register("CmdOrCtrl+Shift+H", move || {
+                    if let Some(window) = show_handle.get_webview_window("main") {
+                        let _ = window.show();
+                        let _ = window.set_focus();
+                    }
+                });
+                let _ = shortcuts.register("CmdOrCtrl+Shift+R", move || {
+                    let service_manager = restart_handle.state::<ServiceManager>();
+                    let unhealthy: Vec<String> = service_manager
+                        .services
+                        .lock()
+                        .unwrap()
+                        .iter()
+                        .filter(|(_, s)| s.status == "error")
+                        .map(|(name, _)| name.clone())
+                        .collect();
+                    for name in unhealthy {
+                        if let Some(descriptor) = service_manager.service_descriptor(&name) {
+                            service_manager.graceful_stop_service_enhanced(&name);
+                            let resource_dir = restart_handle
+                                .path_resolver()
+                                .resource_dir()
+                                .expect("Failed to get resource directory");
+                            let _ = service_manager.start_service(&name, &descriptor.script_path, descriptor.port, &descriptor.readiness.describe(), &resource_dir);
+                        }
+                    }
+                });
+            }
+
             // Start all Python services
             if let Err(e) = service_manager.start_all_services(resource_dir) {
                 eprintln!("Failed to start services: {}", e);
@@ -741,9 +2171,17 @@ impl Clone for ServiceManager {
         Self {
             processes: Arc::clone(&self.processes),
             services: Arc::clone(&self.services),
+            logs: Arc::clone(&self.logs),
+            listeners: Arc::clone(&self.listeners),
+            on_demand_services: Arc::clone(&self.on_demand_services),
+            log_subscribers: Arc::clone(&self.log_subscribers),
+            crash_records: Arc::clone(&self.crash_records),
+            app_handle: Arc::clone(&self.app_handle),
             startup_time: self.startup_time,
-            port_profile: self.port_profile.clone(),
+            port_profile: Arc::clone(&self.port_profile),
             shutdown_in_progress: Arc::clone(&self.shutdown_in_progress),
+            service_registry: Arc::clone(&self.service_registry),
+            max_restart_attempts: self.max_restart_attempts,
         }
     }
 }
\ No newline at end of file